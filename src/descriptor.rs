@@ -0,0 +1,16 @@
+use ::libc;
+use std::os::unix::io::RawFd;
+
+/// Common behaviour shared by the file descriptors owned by a pty
+/// (`Master` and `Slave`).
+pub trait Descriptor {
+    /// Returns the raw file descriptor.
+    fn raw(&self) -> RawFd;
+
+    /// Closes the underlying file descriptor.
+    fn drop(who: &Self) {
+        unsafe {
+            libc::close(who.raw());
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use super::pty::{MasterError, SlaveError};
+
+pub type Result<T> = ::std::result::Result<T, ForkError>;
+
+#[derive(Debug)]
+pub enum ForkError {
+    // the master failed to initialize
+    BadMaster(MasterError),
+    // the slave failed to initialize
+    BadSlave(SlaveError),
+    // setsid() failed
+    SetsidFail,
+    // TIOCSCTTY failed to make the slave the controlling terminal
+    CttyFail(SlaveError),
+    // fork() failed
+    Failure,
+    // pipe() failed while setting up the exec-result pipe
+    PipeFail,
+    // waitpid() failed
+    WaitpidFail,
+    // kill() failed
+    KillFail,
+    // execve() or the pre-exec pty setup failed in the child, reported back
+    // over the exec pipe
+    ExecFailed(io::Error),
+    // reading the exec-result pipe in the parent failed
+    ExecPipeReadFail(io::Error),
+    // the exec-result pipe closed after fewer than 4 bytes, so the errno it
+    // was reporting is unrecoverable; must not be mistaken for the 0-byte
+    // EOF that signals a successful exec
+    ExecPipeTruncated(usize),
+    // called a parent-only method on a Fork::Child
+    IsChild,
+    // called a child-only method on a Fork::Parent
+    IsParent,
+}
+
+impl fmt::Display for ForkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ForkError::BadMaster(ref cause) => write!(f, "bad master: {}", cause),
+            ForkError::BadSlave(ref cause) => write!(f, "bad slave: {}", cause),
+            ForkError::SetsidFail => write!(f, "setsid() failed"),
+            ForkError::CttyFail(ref cause) => write!(f, "TIOCSCTTY failed: {}", cause),
+            ForkError::Failure => write!(f, "fork() failed"),
+            ForkError::PipeFail => write!(f, "pipe() failed"),
+            ForkError::WaitpidFail => write!(f, "waitpid() failed"),
+            ForkError::KillFail => write!(f, "kill() failed"),
+            ForkError::ExecFailed(ref cause) => write!(f, "exec() failed: {}", cause),
+            ForkError::ExecPipeReadFail(ref cause) => write!(f, "reading exec pipe failed: {}", cause),
+            ForkError::ExecPipeTruncated(n) => {
+                write!(f, "exec pipe closed after {} of 4 errno bytes", n)
+            }
+            ForkError::IsChild => write!(f, "not allowed on the child"),
+            ForkError::IsParent => write!(f, "not allowed on the parent"),
+        }
+    }
+}
+
+impl Error for ForkError {
+    fn description(&self) -> &str {
+        "fork error"
+    }
+}
@@ -7,21 +7,251 @@ use ::libc;
 pub use self::err::{ForkError, Result};
 pub use self::pty::{Master, MasterError};
 pub use self::pty::{Slave, SlaveError};
-use std::ffi::CString;
+use std::collections::HashMap;
+use std::env;
+use std::ffi::{CStr, CString};
+use std::io;
+use std::os::unix::io::RawFd;
+use std::ptr;
 
 #[derive(Debug)]
 pub enum Fork {
-    // Parent child's pid and master's pty.
-    Parent(libc::pid_t, Master),
+    // Parent's pid, its pty master, and (on Linux) a pidfd for that pid so
+    // later try_wait/kill calls can't race against pid reuse.
+    Parent(libc::pid_t, Master, Option<RawFd>),
     // Child pid 0.
     Child(Slave),
 }
 
+/// Opens a pidfd for `pid` via `pidfd_open(2)`. Returns `None` on platforms
+/// or kernels that don't support it; callers fall back to pid-based
+/// wait/kill in that case.
+#[cfg(target_os = "linux")]
+fn open_pidfd(pid: libc::pid_t) -> Option<RawFd> {
+    unsafe {
+        match libc::syscall(libc::SYS_pidfd_open, pid, 0) {
+            fd if fd >= 0 => Some(fd as RawFd),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_pidfd(_pid: libc::pid_t) -> Option<RawFd> {
+    None
+}
+
+/// Reconstructs a `waitpid`-style wait status from a `waitid` `siginfo_t`,
+/// so `try_wait`'s pidfd and pid-based paths return the same shape.
+#[cfg(target_os = "linux")]
+fn encode_wait_status(info: &libc::siginfo_t) -> libc::c_int {
+    unsafe {
+        match info.si_code {
+            libc::CLD_EXITED => (info.si_status() & 0xff) << 8,
+            libc::CLD_DUMPED => info.si_status() & 0x7f | 0x80,
+            _ => info.si_status() & 0x7f,
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod encode_wait_status_tests {
+    use super::encode_wait_status;
+
+    // `si_pid`/`si_status` live in the `sigchld` arm of `siginfo_t`'s private
+    // union, behind `si_signo`/`si_errno`/`si_code` plus the padding the
+    // union's pointer-sized alignment requires, with no public setter. Poke
+    // `si_status` at that offset directly rather than depending on the
+    // private layout `encode_wait_status` itself relies on via `si_status()`.
+    fn siginfo(si_code: libc::c_int, si_status: libc::c_int) -> libc::siginfo_t {
+        let mut info: libc::siginfo_t = unsafe { ::std::mem::zeroed() };
+        info.si_code = si_code;
+        unsafe {
+            let int_size = ::std::mem::size_of::<libc::c_int>();
+            let union_align = ::std::mem::align_of::<*mut libc::c_void>();
+            let union_offset = (3 * int_size + union_align - 1) / union_align * union_align;
+            let status_offset = union_offset + 2 * int_size; // si_pid, si_uid, si_status
+            let base = &mut info as *mut libc::siginfo_t as *mut u8;
+            *(base.add(status_offset) as *mut libc::c_int) = si_status;
+        }
+        info
+    }
+
+    #[test]
+    fn exited() {
+        let info = siginfo(libc::CLD_EXITED, 42);
+        let status = encode_wait_status(&info);
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 42);
+    }
+
+    #[test]
+    fn killed() {
+        let info = siginfo(libc::CLD_KILLED, libc::SIGTERM);
+        let status = encode_wait_status(&info);
+        assert!(libc::WIFSIGNALED(status));
+        assert_eq!(libc::WTERMSIG(status), libc::SIGTERM);
+        assert!(!libc::WCOREDUMP(status));
+    }
+
+    #[test]
+    fn dumped() {
+        let info = siginfo(libc::CLD_DUMPED, libc::SIGSEGV);
+        let status = encode_wait_status(&info);
+        assert!(libc::WIFSIGNALED(status));
+        assert_eq!(libc::WTERMSIG(status), libc::SIGSEGV);
+        assert!(libc::WCOREDUMP(status));
+    }
+}
+
+/// Reaps an exited child via `waitid` on its pidfd. Returns `None` when the
+/// kernel lacks pidfd support (`ENOSYS`), so the caller can fall back to the
+/// pid-based path.
+#[cfg(target_os = "linux")]
+fn pidfd_try_wait(fd: RawFd) -> Option<Result<Option<libc::c_int>>> {
+    let mut info: libc::siginfo_t = unsafe { ::std::mem::zeroed() };
+    match unsafe { libc::waitid(libc::P_PIDFD, fd as libc::id_t, &mut info, libc::WEXITED | libc::WNOHANG) } {
+        -1 if io::Error::last_os_error().raw_os_error() == Some(libc::ENOSYS) => None,
+        -1 => Some(Err(ForkError::WaitpidFail)),
+        _ => {
+            Some(Ok(if unsafe { info.si_pid() } == 0 {
+                None
+            } else {
+                Some(encode_wait_status(&info))
+            }))
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pidfd_try_wait(_fd: RawFd) -> Option<Result<Option<libc::c_int>>> {
+    None
+}
+
+/// Sends `signal` via `pidfd_send_signal` on the pidfd. Returns `None` when
+/// the kernel lacks pidfd support (`ENOSYS`), so the caller can fall back to
+/// the pid-based path.
+#[cfg(target_os = "linux")]
+fn pidfd_kill(fd: RawFd, signal: libc::c_int) -> Option<Result<()>> {
+    match unsafe {
+        libc::syscall(libc::SYS_pidfd_send_signal, fd, signal, ptr::null::<libc::siginfo_t>(), 0)
+    } {
+        0 => Some(Ok(())),
+        _ if io::Error::last_os_error().raw_os_error() == Some(libc::ENOSYS) => None,
+        _ => Some(Err(ForkError::KillFail)),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pidfd_kill(_fd: RawFd, _signal: libc::c_int) -> Option<Result<()>> {
+    None
+}
+
+/// Owns every allocation `exec_child` needs, built up front in the parent
+/// (before `fork`) so the child never has to call into the allocator
+/// between `fork` and `execve`. A thread other than the one that forks
+/// can be holding the allocator's lock at the moment of `fork`, and that
+/// lock comes over into the child latched forever since the owning
+/// thread doesn't exist there — any `malloc` in the child then hangs.
+/// This is also why `envp` is built here instead of looping `setenv` in
+/// the child: glibc's `setenv` reallocs `environ` internally.
+struct ExecArgs {
+    program: CString,
+    // Kept alive so `argv`'s first pointer stays valid; not read directly.
+    #[allow(dead_code)]
+    argv0: CString,
+    // Kept alive so `argv`'s pointers into it stay valid; not read directly.
+    #[allow(dead_code)]
+    args: Vec<CString>,
+    argv: Vec<*const libc::c_char>,
+    // Kept alive so `envp`'s pointers into it stay valid; not read directly.
+    #[allow(dead_code)]
+    envp_strings: Vec<CString>,
+    envp: Vec<*const libc::c_char>,
+}
+
+impl ExecArgs {
+    fn new(argv0: &str, program: &str, args: &[&str], envs: &[(&str, &str)]) -> Self {
+        let program = CString::new(program).unwrap_or_default();
+        let argv0 = CString::new(argv0).unwrap_or_default();
+        let args: Vec<CString> = args.iter()
+            .map(|arg| CString::new(*arg).unwrap_or_default())
+            .collect();
+        let mut argv: Vec<*const libc::c_char> = Some(argv0.as_ptr()).into_iter()
+            .chain(args.iter().map(|arg| arg.as_ptr()))
+            .collect();
+        argv.push(ptr::null());
+
+        // Starts from the inherited environment so `envs` behaves like the
+        // `setenv` calls it replaces: adding or overriding the given keys
+        // without dropping everything else already in the environment.
+        let mut vars: HashMap<String, String> = env::vars().collect();
+        for &(key, value) in envs {
+            vars.insert(key.to_owned(), value.to_owned());
+        }
+        let envp_strings: Vec<CString> = vars.iter()
+            .filter_map(|(key, value)| CString::new(format!("{}={}", key, value)).ok())
+            .collect();
+        let mut envp: Vec<*const libc::c_char> = envp_strings.iter().map(|s| s.as_ptr()).collect();
+        envp.push(ptr::null());
+
+        ExecArgs {
+            program: program,
+            argv0: argv0,
+            args: args,
+            argv: argv,
+            envp_strings: envp_strings,
+            envp: envp,
+        }
+    }
+}
+
+/// The handful of passwd(5) fields `login_shell` needs.
+struct Pwd {
+    name: String,
+    home: String,
+    shell: String,
+}
+
+/// Looks up the passwd entry for the calling user via `getpwuid_r`, into a
+/// stack buffer. Returns `None` if the lookup fails or the account has no
+/// entry.
+fn passwd_entry() -> Option<Pwd> {
+    let mut pwd: libc::passwd = unsafe { ::std::mem::zeroed() };
+    let mut buf = [0 as libc::c_char; 4096];
+    let mut result: *mut libc::passwd = ptr::null_mut();
+
+    let rc = unsafe {
+        libc::getpwuid_r(libc::getuid(), &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result)
+    };
+    if rc != 0 || result.is_null() {
+        return None;
+    }
+
+    unsafe {
+        Some(Pwd {
+            name: CStr::from_ptr(pwd.pw_name).to_string_lossy().into_owned(),
+            home: CStr::from_ptr(pwd.pw_dir).to_string_lossy().into_owned(),
+            shell: CStr::from_ptr(pwd.pw_shell).to_string_lossy().into_owned(),
+        })
+    }
+}
+
 impl Fork {
     /// The constructor function `new` forks the program
     /// and returns the current pid.
     pub fn new(path: &'static str) -> Result<Self> {
-        match Master::new(CString::new(path).ok().unwrap_or_default().as_ptr()) {
+        Fork::new_sized(path, None)
+    }
+
+    /// Like `new`, but also applies `winsize` to the slave before the child
+    /// runs, so full-screen programs see the right dimensions from the start.
+    pub fn with_size(path: &'static str, winsize: libc::winsize) -> Result<Self> {
+        Fork::new_sized(path, Some(winsize))
+    }
+
+    fn new_sized(path: &'static str, winsize: Option<libc::winsize>) -> Result<Self> {
+        match unsafe { Master::new(CString::new(path).ok().unwrap_or_default().as_ptr()) } {
             Err(cause) => Err(ForkError::BadMaster(cause)),
             Ok(master) => unsafe {
                 if let Some(cause) = master.grantpt().err().or(master.unlockpt().err()) {
@@ -32,10 +262,10 @@ impl Fork {
                         0 => {
                             match master.ptsname() {
                                 Err(cause) => Err(ForkError::BadMaster(cause)),
-                                Ok(name) => Fork::from_pts(name),
+                                Ok(name) => Fork::from_pts(name, winsize.as_ref()),
                             }
                         }
-                        pid => Ok(Fork::Parent(pid, master)),
+                        pid => Ok(Fork::Parent(pid, master, open_pidfd(pid))),
                     }
                 }
             },
@@ -45,7 +275,7 @@ impl Fork {
     /// The constructor function `from_pts` is a private
     /// extension from the constructor function `new` who
     /// prepares and returns the child.
-    fn from_pts(ptsname: *const ::libc::c_char) -> Result<Self> {
+    fn from_pts(ptsname: *const ::libc::c_char, winsize: Option<&libc::winsize>) -> Result<Self> {
         unsafe {
             // make parent process the session leader
             // so e.g. Ctrl-C is sent to the slave
@@ -55,6 +285,14 @@ impl Fork {
                 match Slave::new(ptsname) {
                     Err(cause) => Err(ForkError::BadSlave(cause)),
                     Ok(slave) => {
+                        if let Err(cause) = slave.set_controlling_tty() {
+                            return Err(ForkError::CttyFail(cause));
+                        }
+                        if let Some(ws) = winsize {
+                            if let Err(cause) = slave.set_window_size(ws) {
+                                return Err(ForkError::BadSlave(cause));
+                            }
+                        }
                         slave.dup2(libc::STDIN_FILENO)
                             .and_then(|_| slave.dup2(libc::STDOUT_FILENO))
                             .and_then(|_| slave.dup2(libc::STDERR_FILENO))
@@ -66,18 +304,281 @@ impl Fork {
         }
     }
 
+    /// Forks and `execve`s `program` with `args` and `envs` in the child.
+    /// Exec failures are reported back to the parent over a close-on-exec
+    /// pipe, so a dead `execve` isn't mistaken for a live child. `program`
+    /// is passed to `execve` as a path; `PATH` is not searched.
+    pub fn spawn(path: &'static str, program: &str, args: &[&str], envs: &[(&str, &str)]) -> Result<Self> {
+        Fork::spawn_sized(path, program, program, args, envs, None)
+    }
+
+    /// Like `spawn`, but also applies `winsize` to the slave before exec.
+    pub fn spawn_with_size(path: &'static str,
+                            program: &str,
+                            args: &[&str],
+                            envs: &[(&str, &str)],
+                            winsize: libc::winsize)
+                            -> Result<Self> {
+        Fork::spawn_sized(path, program, program, args, envs, Some(winsize))
+    }
+
+    fn spawn_sized(path: &'static str,
+                   argv0: &str,
+                   program: &str,
+                   args: &[&str],
+                   envs: &[(&str, &str)],
+                   winsize: Option<libc::winsize>)
+                   -> Result<Self> {
+        // Built now, before `fork`, so the child's post-fork path never
+        // touches the allocator (see `ExecArgs`'s doc comment).
+        let exec_args = ExecArgs::new(argv0, program, args, envs);
+
+        let mut pipe_fds = [0 as libc::c_int; 2];
+        if unsafe { libc::pipe(pipe_fds.as_mut_ptr()) } == -1 {
+            return Err(ForkError::PipeFail);
+        }
+        let (read_fd, write_fd) = (pipe_fds[0], pipe_fds[1]);
+        unsafe {
+            let flags = libc::fcntl(write_fd, libc::F_GETFD);
+            libc::fcntl(write_fd, libc::F_SETFD, flags | libc::FD_CLOEXEC);
+        }
+
+        match unsafe { Master::new(CString::new(path).ok().unwrap_or_default().as_ptr()) } {
+            Err(cause) => {
+                unsafe {
+                    libc::close(read_fd);
+                    libc::close(write_fd);
+                }
+                Err(ForkError::BadMaster(cause))
+            }
+            Ok(master) => unsafe {
+                if let Some(cause) = master.grantpt().err().or(master.unlockpt().err()) {
+                    libc::close(read_fd);
+                    libc::close(write_fd);
+                    Descriptor::drop(&master);
+                    Err(ForkError::BadMaster(cause))
+                } else {
+                    match libc::fork() {
+                        -1 => {
+                            libc::close(read_fd);
+                            libc::close(write_fd);
+                            Descriptor::drop(&master);
+                            Err(ForkError::Failure)
+                        }
+                        0 => {
+                            libc::close(read_fd);
+                            match master.ptsname() {
+                                Err(cause) => {
+                                    Fork::report_child_setup_failure(write_fd, ForkError::BadMaster(cause))
+                                }
+                                Ok(name) => {
+                                    match Fork::from_pts(name, winsize.as_ref()) {
+                                        Ok(Fork::Child(ref slave)) => {
+                                            // Neither fd is O_CLOEXEC, and the child has
+                                            // already dup2'd the slave onto 0/1/2, so close
+                                            // both the inherited master and the slave's
+                                            // original fd before exec, or the spawned
+                                            // program (and its children) would inherit
+                                            // direct access to its own pty's master side.
+                                            Descriptor::drop(&master);
+                                            Descriptor::drop(slave);
+                                            Fork::exec_child(&exec_args, write_fd)
+                                        }
+                                        Ok(Fork::Parent(..)) => {
+                                            Fork::report_child_setup_failure(write_fd, ForkError::Failure)
+                                        }
+                                        Err(cause) => Fork::report_child_setup_failure(write_fd, cause),
+                                    }
+                                }
+                            }
+                        }
+                        pid => {
+                            // Opened immediately off the fork()-returns-pid
+                            // arm, before the blocking read below, so it
+                            // can't lose a pid-reuse race against a host
+                            // supervisor reaping this child in the meantime.
+                            let pidfd = open_pidfd(pid);
+                            libc::close(write_fd);
+                            let result = Fork::read_exec_result(read_fd);
+                            libc::close(read_fd);
+                            match result {
+                                Ok(Some(errno)) => {
+                                    let mut status = 0;
+                                    libc::waitpid(pid, &mut status, 0);
+                                    if let Some(fd) = pidfd {
+                                        libc::close(fd);
+                                    }
+                                    Descriptor::drop(&master);
+                                    Err(ForkError::ExecFailed(io::Error::from_raw_os_error(errno)))
+                                }
+                                Ok(None) => Ok(Fork::Parent(pid, master, pidfd)),
+                                Err(cause) => {
+                                    // A truncated read in particular means the
+                                    // child died mid-write, so reap it here too
+                                    // rather than leaving a zombie behind — the
+                                    // caller never gets a `Fork` to `wait` on.
+                                    let mut status = 0;
+                                    libc::waitpid(pid, &mut status, 0);
+                                    if let Some(fd) = pidfd {
+                                        libc::close(fd);
+                                    }
+                                    Descriptor::drop(&master);
+                                    Err(cause)
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+        }
+    }
+
+    /// Reads the 4-byte errno off the exec-result pipe, retrying on
+    /// `EINTR`. Returns `Ok(None)` on a clean 0-byte EOF (the write end
+    /// closed on a successful `execve`), `Ok(Some(errno))` if the child
+    /// reported a failure, `Err(ExecPipeTruncated)` if the pipe closed
+    /// after 1-3 bytes (the child died mid-write; this must not be lumped
+    /// in with the 0-byte success case), or `Err` if the read itself failed
+    /// for another reason.
+    fn read_exec_result(read_fd: libc::c_int) -> Result<Option<i32>> {
+        let mut buf = [0u8; 4];
+        let mut filled = 0;
+        while filled < buf.len() {
+            let n = unsafe {
+                libc::read(read_fd,
+                           buf[filled..].as_mut_ptr() as *mut libc::c_void,
+                           (buf.len() - filled) as libc::size_t)
+            };
+            match n {
+                0 => break,
+                -1 => {
+                    let cause = io::Error::last_os_error();
+                    if cause.kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    return Err(ForkError::ExecPipeReadFail(cause));
+                }
+                n => filled += n as usize,
+            }
+        }
+        match filled {
+            0 => Ok(None),
+            n if n == buf.len() => Ok(Some(i32::from_be_bytes(buf))),
+            n => Err(ForkError::ExecPipeTruncated(n)),
+        }
+    }
+
+    /// Writes the 4-byte `errno` to the exec pipe, retrying the write on
+    /// `EINTR` so a signal landing mid-write can't truncate it into a
+    /// plain EOF the parent mistakes for a successful exec. Always exits
+    /// nonzero afterwards, even if the write itself failed for some other
+    /// reason, so the parent can at least detect the dead child via
+    /// `waitpid`/`try_wait`.
+    fn exit_with_exec_result(write_fd: libc::c_int, errno: i32) -> ! {
+        unsafe {
+            let bytes = errno.to_be_bytes();
+            let mut written = 0;
+            while written < bytes.len() {
+                match libc::write(write_fd,
+                                   bytes[written..].as_ptr() as *const libc::c_void,
+                                   (bytes.len() - written) as libc::size_t) {
+                    -1 => {
+                        if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                            continue;
+                        }
+                        break;
+                    }
+                    n => written += n as usize,
+                }
+            }
+            libc::_exit(127);
+        }
+    }
+
+    /// Reports a pre-exec setup failure (pty/session/ctty/window-size) back
+    /// to the parent over the exec pipe before exiting, the same way
+    /// `exec_child` reports an `execve` failure. Without this, the parent's
+    /// `read` would see a plain EOF — indistinguishable from a successful
+    /// exec — and hand back a `Fork::Parent` for a child that's already
+    /// dead.
+    fn report_child_setup_failure(write_fd: libc::c_int, cause: ForkError) -> ! {
+        let errno = match cause {
+            ForkError::BadMaster(ref e) => e.raw_os_error(),
+            ForkError::BadSlave(ref e) => e.raw_os_error(),
+            ForkError::CttyFail(ref e) => e.raw_os_error(),
+            _ => None,
+        }.unwrap_or(libc::EIO);
+        Fork::exit_with_exec_result(write_fd, errno)
+    }
+
+    /// Execs `program` in the current (child) process, writing the raw errno
+    /// to `write_fd` if `execve` returns (i.e. failed). Never returns on
+    /// success, since the kernel replaces the process image. `exec_args`
+    /// must already hold every `CString`/pointer this needs, built by the
+    /// parent before `fork` — including `envp`, so the child never has to
+    /// touch the allocator via `setenv` between `fork` and exec.
+    fn exec_child(exec_args: &ExecArgs, write_fd: libc::c_int) -> ! {
+        unsafe {
+            libc::execve(exec_args.program.as_ptr(), exec_args.argv.as_ptr(), exec_args.envp.as_ptr());
+
+            // execve only returns on failure
+            let errno = io::Error::last_os_error().raw_os_error().unwrap_or(-1);
+            Fork::exit_with_exec_result(write_fd, errno)
+        }
+    }
+
     /// The constructor function `from_ptmx` forks the program
     /// and returns the current pid for a default PTMX's path.
     pub fn from_ptmx() -> Result<Self> {
         Fork::new(::DEFAULT_PTMX)
     }
 
+    /// Spawns the caller's login shell on a fresh pty: resolves the shell
+    /// from `$SHELL`, falling back to the passwd database and then
+    /// `/bin/sh`, sets `TERM`/`SHELL` (and `HOME`/`LOGNAME`/`USER` when the
+    /// passwd lookup succeeds), and runs it as a login shell (`argv[0]`
+    /// prefixed with `-`).
+    pub fn login_shell() -> Result<Self> {
+        Fork::login_shell_sized(None)
+    }
+
+    /// Like `login_shell`, but also applies `winsize` to the slave before
+    /// exec.
+    pub fn login_shell_with_size(winsize: libc::winsize) -> Result<Self> {
+        Fork::login_shell_sized(Some(winsize))
+    }
+
+    fn login_shell_sized(winsize: Option<libc::winsize>) -> Result<Self> {
+        let pwd = passwd_entry();
+
+        let shell = env::var("SHELL")
+            .ok()
+            .filter(|shell| !shell.is_empty())
+            .or_else(|| pwd.as_ref().map(|pwd| pwd.shell.clone()))
+            .filter(|shell| !shell.is_empty())
+            .unwrap_or_else(|| "/bin/sh".to_owned());
+
+        let mut envs: Vec<(&str, String)> = vec![
+            ("TERM", env::var("TERM").unwrap_or_else(|_| "xterm".to_owned())),
+            ("SHELL", shell.clone()),
+        ];
+        if let Some(ref pwd) = pwd {
+            envs.push(("HOME", pwd.home.clone()));
+            envs.push(("LOGNAME", pwd.name.clone()));
+            envs.push(("USER", pwd.name.clone()));
+        }
+        let envs: Vec<(&str, &str)> = envs.iter().map(|&(key, ref value)| (key, value.as_str())).collect();
+
+        let argv0 = format!("-{}", shell.rsplit('/').next().unwrap_or(&shell));
+        Fork::spawn_sized(::DEFAULT_PTMX, &argv0, &shell, &[], &envs, winsize)
+    }
+
     /// Waits until slave is terminated (blocking call)
     /// Returns exit status of slave process
     pub fn wait(&self) -> Result<(libc::c_int)> {
         match *self {
             Fork::Child(_) => Err(ForkError::IsChild),
-            Fork::Parent(pid, _) => {
+            Fork::Parent(pid, _, _) => {
                 let mut status = 0;
                 loop {
                     unsafe {
@@ -92,12 +593,86 @@ impl Fork {
         }
     }
 
+    /// Polls for the child's exit status without blocking. Returns `None`
+    /// while the child is still running. Prefers `waitid` on the pidfd when
+    /// present, falling back to the pid-based path on older kernels.
+    pub fn try_wait(&self) -> Result<Option<libc::c_int>> {
+        match *self {
+            Fork::Child(_) => Err(ForkError::IsChild),
+            Fork::Parent(pid, _, pidfd) => {
+                if let Some(result) = pidfd.and_then(pidfd_try_wait) {
+                    return result;
+                }
+                let mut status = 0;
+                unsafe {
+                    match libc::waitpid(pid, &mut status, libc::WNOHANG) {
+                        0 => Ok(None),
+                        -1 => Err(ForkError::WaitpidFail),
+                        _ => Ok(Some(status)),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sends `signal` to the child. Prefers `pidfd_send_signal` on the pidfd
+    /// when present, falling back to the pid-based path on older kernels.
+    pub fn kill(&self, signal: libc::c_int) -> Result<()> {
+        match *self {
+            Fork::Child(_) => Err(ForkError::IsChild),
+            Fork::Parent(pid, _, pidfd) => {
+                if let Some(result) = pidfd.and_then(|fd| pidfd_kill(fd, signal)) {
+                    return result;
+                }
+                if unsafe { libc::kill(pid, signal) } == -1 {
+                    Err(ForkError::KillFail)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Exposes the raw pidfd for the child, when the kernel supports
+    /// `pidfd_open`, so it can be registered in an `epoll`/`poll` loop for
+    /// readiness-based exit notification. This fd is owned by `self` and
+    /// closed by `Fork`'s `Drop` impl; deregister it from the event loop as
+    /// needed, but don't close it directly, or `Drop` will double-close it.
+    pub fn pidfd(&self) -> Result<Option<RawFd>> {
+        match *self {
+            Fork::Child(_) => Err(ForkError::IsChild),
+            Fork::Parent(_, _, pidfd) => Ok(pidfd),
+        }
+    }
+
+    /// Applies `(rows, cols, xpixel, ypixel)` to the master's pty, e.g. when
+    /// propagating a `SIGWINCH` from the parent's own terminal.
+    pub fn set_window_size(&self, rows: u16, cols: u16, xpixel: u16, ypixel: u16) -> Result<()> {
+        match *self {
+            Fork::Child(_) => Err(ForkError::IsChild),
+            Fork::Parent(_, ref master, _) => {
+                master.set_window_size(rows, cols, xpixel, ypixel)
+                    .or_else(|e| Err(ForkError::BadMaster(e)))
+            }
+        }
+    }
+
+    /// Reads the master's current pty terminal size.
+    pub fn window_size(&self) -> Result<libc::winsize> {
+        match *self {
+            Fork::Child(_) => Err(ForkError::IsChild),
+            Fork::Parent(_, ref master, _) => {
+                master.window_size().or_else(|e| Err(ForkError::BadMaster(e)))
+            }
+        }
+    }
+
     /// The function `is_parent` returns the pid or parent
     /// or none.
     pub fn is_parent(&self) -> Result<Master> {
         match *self {
             Fork::Child(_) => Err(ForkError::IsChild),
-            Fork::Parent(_, ref master) => Ok(master.clone()),
+            Fork::Parent(_, ref master, _) => Ok(master.clone()),
         }
     }
 
@@ -105,7 +680,7 @@ impl Fork {
     /// or none.
     pub fn is_child(&self) -> Result<&Slave> {
         match *self {
-            Fork::Parent(_, _) => Err(ForkError::IsParent),
+            Fork::Parent(_, _, _) => Err(ForkError::IsParent),
             Fork::Child(ref slave) => Ok(slave),
         }
     }
@@ -114,8 +689,42 @@ impl Fork {
 impl Drop for Fork {
     fn drop(&mut self) {
         match *self {
-            Fork::Parent(_, ref master) => Descriptor::drop(master),
+            Fork::Parent(_, ref master, pidfd) => {
+                Descriptor::drop(master);
+                if let Some(fd) = pidfd {
+                    unsafe {
+                        libc::close(fd);
+                    }
+                }
+            }
             _ => {}
         }
     }
 }
+
+#[cfg(test)]
+mod spawn_tests {
+    use super::{Fork, ForkError};
+    use ::libc;
+
+    #[test]
+    fn spawn_missing_program_reports_exec_failed() {
+        match Fork::spawn(::DEFAULT_PTMX, "/nonexistent-program-xyz", &[], &[]) {
+            Err(ForkError::ExecFailed(_)) => {}
+            other => panic!("expected ExecFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn spawn_true_and_false_report_exit_status() {
+        let fork = Fork::spawn(::DEFAULT_PTMX, "/bin/true", &[], &[]).expect("spawn /bin/true");
+        let status = fork.wait().expect("wait on /bin/true");
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 0);
+
+        let fork = Fork::spawn(::DEFAULT_PTMX, "/bin/false", &[], &[]).expect("spawn /bin/false");
+        let status = fork.wait().expect("wait on /bin/false");
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 1);
+    }
+}
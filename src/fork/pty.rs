@@ -0,0 +1,204 @@
+use ::descriptor::Descriptor;
+use ::libc;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::os::unix::io::RawFd;
+
+pub type Result<T> = ::std::result::Result<T, MasterError>;
+
+/// Wraps an `io::Error` raised while creating or driving the pty master.
+#[derive(Debug)]
+pub struct MasterError(io::Error);
+
+impl MasterError {
+    /// The underlying OS error code, when there is one.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        self.0.raw_os_error()
+    }
+}
+
+impl fmt::Display for MasterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for MasterError {
+    fn description(&self) -> &str {
+        "master error"
+    }
+}
+
+/// Wraps an `io::Error` raised while creating or driving the pty slave.
+#[derive(Debug)]
+pub struct SlaveError(io::Error);
+
+impl SlaveError {
+    /// The underlying OS error code, when there is one.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        self.0.raw_os_error()
+    }
+}
+
+impl fmt::Display for SlaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for SlaveError {
+    fn description(&self) -> &str {
+        "slave error"
+    }
+}
+
+/// The master end of a pty pair.
+#[derive(Debug)]
+pub struct Master {
+    fd: RawFd,
+}
+
+impl Master {
+    /// Opens `path` (usually `/dev/ptmx`) as the master side of a pty.
+    ///
+    /// # Safety
+    ///
+    /// `path` must be a non-null, NUL-terminated C string valid for the
+    /// duration of the call, e.g. the pointer from a live `CString`.
+    pub unsafe fn new(path: *const libc::c_char) -> Result<Self> {
+        match libc::open(path, libc::O_RDWR) {
+            -1 => Err(MasterError(io::Error::last_os_error())),
+            fd => Ok(Master { fd: fd }),
+        }
+    }
+
+    /// Grants access to the slave pty.
+    pub unsafe fn grantpt(&self) -> Result<()> {
+        if libc::grantpt(self.fd) == -1 {
+            Err(MasterError(io::Error::last_os_error()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Unlocks the slave pty so it can be opened.
+    pub unsafe fn unlockpt(&self) -> Result<()> {
+        if libc::unlockpt(self.fd) == -1 {
+            Err(MasterError(io::Error::last_os_error()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the path of the associated slave pty.
+    pub unsafe fn ptsname(&self) -> Result<*const libc::c_char> {
+        let name = libc::ptsname(self.fd);
+        if name.is_null() {
+            Err(MasterError(io::Error::last_os_error()))
+        } else {
+            Ok(name)
+        }
+    }
+
+    /// Sets the pty's terminal size via `TIOCSWINSZ`, e.g. in response to
+    /// `SIGWINCH`.
+    pub fn set_window_size(&self, rows: u16, cols: u16, xpixel: u16, ypixel: u16) -> Result<()> {
+        let ws = libc::winsize {
+            ws_row: rows,
+            ws_col: cols,
+            ws_xpixel: xpixel,
+            ws_ypixel: ypixel,
+        };
+        unsafe {
+            if libc::ioctl(self.fd, libc::TIOCSWINSZ, &ws) == -1 {
+                Err(MasterError(io::Error::last_os_error()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Reads the pty's current terminal size via `TIOCGWINSZ`.
+    pub fn window_size(&self) -> Result<libc::winsize> {
+        let mut ws: libc::winsize = unsafe { ::std::mem::zeroed() };
+        unsafe {
+            if libc::ioctl(self.fd, libc::TIOCGWINSZ, &mut ws) == -1 {
+                Err(MasterError(io::Error::last_os_error()))
+            } else {
+                Ok(ws)
+            }
+        }
+    }
+}
+
+impl Clone for Master {
+    fn clone(&self) -> Self {
+        Master { fd: self.fd }
+    }
+}
+
+impl Descriptor for Master {
+    fn raw(&self) -> RawFd {
+        self.fd
+    }
+}
+
+/// The slave end of a pty pair, owned by the child process.
+#[derive(Debug)]
+pub struct Slave {
+    fd: RawFd,
+}
+
+impl Slave {
+    /// Opens the slave pty named by `ptsname`.
+    pub unsafe fn new(ptsname: *const libc::c_char) -> ::std::result::Result<Self, SlaveError> {
+        match libc::open(ptsname, libc::O_RDWR) {
+            -1 => Err(SlaveError(io::Error::last_os_error())),
+            fd => Ok(Slave { fd: fd }),
+        }
+    }
+
+    /// Duplicates the slave's file descriptor onto `target`.
+    pub fn dup2(&self, target: libc::c_int) -> ::std::result::Result<(), SlaveError> {
+        unsafe {
+            if libc::dup2(self.fd, target) == -1 {
+                Err(SlaveError(io::Error::last_os_error()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Sets the slave's terminal size via `TIOCSWINSZ`, applied before the
+    /// child takes over the pty.
+    pub fn set_window_size(&self, ws: &libc::winsize) -> ::std::result::Result<(), SlaveError> {
+        unsafe {
+            if libc::ioctl(self.fd, libc::TIOCSWINSZ, ws) == -1 {
+                Err(SlaveError(io::Error::last_os_error()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Makes this pty the controlling terminal of the calling (session
+    /// leader) process via `TIOCSCTTY`, so job control and signal delivery
+    /// (Ctrl-C, Ctrl-Z, `SIGWINCH`) reach the child.
+    pub fn set_controlling_tty(&self) -> ::std::result::Result<(), SlaveError> {
+        unsafe {
+            if libc::ioctl(self.fd, libc::TIOCSCTTY as _, 0) == -1 {
+                Err(SlaveError(io::Error::last_os_error()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Descriptor for Slave {
+    fn raw(&self) -> RawFd {
+        self.fd
+    }
+}
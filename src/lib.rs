@@ -0,0 +1,9 @@
+extern crate libc;
+
+pub mod fork;
+mod descriptor;
+
+pub use fork::Fork;
+
+/// Default path to the pty multiplexer device.
+pub const DEFAULT_PTMX: &'static str = "/dev/ptmx";